@@ -0,0 +1,122 @@
+//! A bounded-size recency cache for corpora whose testcases can be unloaded
+//! back to their backing storage, e.g. `OnDiskCorpus` with a large on-disk
+//! corpus and a small in-memory working set.
+
+use alloc::collections::VecDeque;
+use core::cell::RefCell;
+
+/// Tracks which testcase indices are currently loaded, and in what order
+/// they were last touched, so a corpus can keep at most `max_loaded` of them
+/// in memory at once.
+///
+/// Recency is tracked behind a `RefCell` so read-only accessors like
+/// `Corpus::get`/`current_testcase` can still mark a testcase as
+/// most-recently-used.
+pub struct TestcaseCache {
+    max_loaded: usize,
+    recency: RefCell<VecDeque<usize>>,
+}
+
+impl TestcaseCache {
+    /// Creates a cache that evicts once more than `max_loaded` testcases are loaded.
+    pub fn new(max_loaded: usize) -> Self {
+        Self {
+            max_loaded,
+            recency: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    pub fn max_loaded(&self) -> usize {
+        self.max_loaded
+    }
+
+    pub fn set_max_loaded(&mut self, max_loaded: usize) {
+        self.max_loaded = max_loaded;
+    }
+
+    /// How many testcase indices this cache currently considers loaded.
+    pub fn loaded_count(&self) -> usize {
+        self.recency.borrow().len()
+    }
+
+    /// Marks `idx` as the most-recently-used loaded testcase.
+    pub fn touch(&self, idx: usize) {
+        let mut recency = self.recency.borrow_mut();
+        if let Some(pos) = recency.iter().position(|&i| i == idx) {
+            recency.remove(pos);
+        }
+        recency.push_back(idx);
+    }
+
+    /// Forgets `idx`, e.g. once it has been evicted or removed from the corpus.
+    pub fn forget(&self, idx: usize) {
+        let mut recency = self.recency.borrow_mut();
+        if let Some(pos) = recency.iter().position(|&i| i == idx) {
+            recency.remove(pos);
+        }
+    }
+
+    /// Accounts for `removed` having been removed from the underlying
+    /// `Vec` (which shifts every later index down by one): forgets
+    /// `removed` and renumbers every recency entry greater than it.
+    pub fn remove_and_reindex(&self, removed: usize) {
+        let mut recency = self.recency.borrow_mut();
+        recency.retain(|&i| i != removed);
+        for i in recency.iter_mut() {
+            if *i > removed {
+                *i -= 1;
+            }
+        }
+    }
+
+    /// If the working set is over budget, returns (and forgets) the
+    /// least-recently-used loaded index the caller should evict.
+    pub fn evict_candidate(&self) -> Option<usize> {
+        let mut recency = self.recency.borrow_mut();
+        if recency.len() > self.max_loaded {
+            recency.pop_front()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = TestcaseCache::new(2);
+        cache.touch(0);
+        cache.touch(1);
+        assert_eq!(cache.evict_candidate(), None);
+        cache.touch(2);
+        assert_eq!(cache.evict_candidate(), Some(0));
+        assert_eq!(cache.loaded_count(), 2);
+    }
+
+    #[test]
+    fn test_touch_refreshes_recency() {
+        let cache = TestcaseCache::new(2);
+        cache.touch(0);
+        cache.touch(1);
+        cache.touch(0); // 0 is now most-recently-used again
+        cache.touch(2);
+        assert_eq!(cache.evict_candidate(), Some(1));
+    }
+
+    #[test]
+    fn test_remove_and_reindex_shifts_later_indices_down() {
+        let mut cache = TestcaseCache::new(10);
+        cache.touch(0);
+        cache.touch(1);
+        cache.touch(2);
+        // Removing index 1 should drop it and shift 2 down to 1.
+        cache.remove_and_reindex(1);
+        cache.set_max_loaded(0);
+        assert_eq!(cache.evict_candidate(), Some(0));
+        assert_eq!(cache.evict_candidate(), Some(1));
+        assert_eq!(cache.evict_candidate(), None);
+    }
+}
@@ -0,0 +1,237 @@
+//! An optional encrypting `CorpusStorage` decorator, for fuzzing targets
+//! handling sensitive seed data. Wraps any other `CorpusStorage` and
+//! transparently encrypts testcase bytes at rest with a ChaCha20-style
+//! stream cipher, so `Testcase::load_from_disk`/`OnDiskCorpus::add` work
+//! unchanged: encryption is purely a backend choice.
+
+use alloc::vec::Vec;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::corpus::storage::CorpusStorage;
+use crate::AflError;
+
+const MAGIC: [u8; 4] = *b"AFLE";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN;
+
+/// A `CorpusStorage` decorator that encrypts every testcase's bytes with a
+/// 256-bit key before writing them to the wrapped storage, and decrypts them
+/// on read. Each file is prefixed with a small header (magic, version, and a
+/// per-file 12-byte nonce) ahead of the ciphertext.
+pub struct EncryptedStorage<S>
+where
+    S: CorpusStorage,
+{
+    inner: S,
+    key: [u8; 32],
+}
+
+impl<S> EncryptedStorage<S>
+where
+    S: CorpusStorage,
+{
+    pub fn new(inner: S, key: [u8; 32]) -> Self {
+        Self { inner, key }
+    }
+}
+
+impl<S> CorpusStorage for EncryptedStorage<S>
+where
+    S: CorpusStorage,
+{
+    fn read(&self, path: &Path) -> Result<Vec<u8>, AflError> {
+        let raw = self.inner.read(path)?;
+        if raw.len() < HEADER_LEN {
+            return Err(AflError::IllegalState(format!(
+                "{:?}: truncated encrypted testcase",
+                path
+            )));
+        }
+        if raw[0..MAGIC.len()] != MAGIC {
+            return Err(AflError::IllegalState(format!(
+                "{:?}: not an encrypted testcase (bad magic)",
+                path
+            )));
+        }
+        if raw[MAGIC.len()] != VERSION {
+            return Err(AflError::IllegalState(format!(
+                "{:?}: unsupported encrypted testcase version {}",
+                path,
+                raw[MAGIC.len()]
+            )));
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&raw[MAGIC.len() + 1..HEADER_LEN]);
+        let mut plaintext = raw[HEADER_LEN..].to_vec();
+        chacha20_xor(&self.key, &nonce, 0, &mut plaintext);
+        Ok(plaintext)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), AflError> {
+        let nonce = fresh_nonce();
+        let mut ciphertext = data.to_vec();
+        chacha20_xor(&self.key, &nonce, 0, &mut ciphertext);
+
+        let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        out.extend_from_slice(&MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        self.inner.write(path, &out)
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), AflError> {
+        self.inner.remove(path)
+    }
+
+    fn list(&self) -> Result<Vec<PathBuf>, AflError> {
+        self.inner.list()
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.inner.exists(path)
+    }
+}
+
+static NONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Generates a nonce that won't repeat for a given key within a process,
+/// mixing a monotonic counter with the wall clock.
+fn fresh_nonce() -> [u8; NONCE_LEN] {
+    let counter = NONCE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    // The counter takes 8 bytes on its own, so no two calls share a nonce.
+    let clock = splitmix64(nanos);
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[0..8].copy_from_slice(&counter.to_le_bytes());
+    nonce[8..12].copy_from_slice(&clock.to_le_bytes()[0..4]);
+    nonce
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = x;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// XORs `data` in place with a ChaCha20 keystream, as specified in RFC 8439.
+/// Used both to encrypt (plaintext -> ciphertext) and decrypt (ciphertext ->
+/// plaintext), since XOR with the same keystream is its own inverse.
+fn chacha20_xor(key: &[u8; 32], nonce: &[u8; NONCE_LEN], initial_counter: u32, data: &mut [u8]) {
+    let mut key_words = [0u32; 8];
+    for (word, bytes) in key_words.iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    let mut nonce_words = [0u32; 3];
+    for (word, bytes) in nonce_words.iter_mut().zip(nonce.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    let mut counter = initial_counter;
+    for chunk in data.chunks_mut(64) {
+        let keystream = chacha20_block(&key_words, counter, &nonce_words);
+        for (byte, ks) in chunk.iter_mut().zip(keystream.iter()) {
+            *byte ^= ks;
+        }
+        counter = counter.wrapping_add(1);
+    }
+}
+
+fn chacha20_block(key: &[u32; 8], counter: u32, nonce: &[u32; 3]) -> [u8; 64] {
+    let mut state = [0u32; 16];
+    state[0] = 0x6170_7865;
+    state[1] = 0x3320_646e;
+    state[2] = 0x7962_2d32;
+    state[3] = 0x6b20_6574;
+    state[4..12].copy_from_slice(key);
+    state[12] = counter;
+    state[13..16].copy_from_slice(nonce);
+
+    let mut working = state;
+    for _ in 0..10 {
+        quarter_round(&mut working, 0, 4, 8, 12);
+        quarter_round(&mut working, 1, 5, 9, 13);
+        quarter_round(&mut working, 2, 6, 10, 14);
+        quarter_round(&mut working, 3, 7, 11, 15);
+        quarter_round(&mut working, 0, 5, 10, 15);
+        quarter_round(&mut working, 1, 6, 11, 12);
+        quarter_round(&mut working, 2, 7, 8, 13);
+        quarter_round(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; 64];
+    for i in 0..16 {
+        let word = working[i].wrapping_add(state[i]);
+        out[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(16);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(12);
+
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(8);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(7);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::InMemoryStorage;
+
+    #[test]
+    fn test_roundtrip_with_correct_key() {
+        let key = [7u8; 32];
+        let storage = EncryptedStorage::new(InMemoryStorage::new(), key);
+        let path = PathBuf::from("id_0");
+        storage.write(&path, b"super secret seed").unwrap();
+        assert_eq!(storage.read(&path).unwrap(), b"super secret seed");
+    }
+
+    #[test]
+    fn test_cannot_read_back_with_wrong_key() {
+        let path = PathBuf::from("id_0");
+        let inner = InMemoryStorage::new();
+
+        let writer = EncryptedStorage::new(inner, [1u8; 32]);
+        writer.write(&path, b"super secret seed").unwrap();
+
+        // Re-wrap the same underlying bytes with the wrong key.
+        let raw = writer.inner.read(&path).unwrap();
+        let wrong_inner = InMemoryStorage::new();
+        wrong_inner.write(&path, &raw).unwrap();
+        let reader = EncryptedStorage::new(wrong_inner, [2u8; 32]);
+
+        assert_ne!(reader.read(&path).unwrap(), b"super secret seed");
+    }
+
+    #[test]
+    fn test_header_is_prepended() {
+        let storage = EncryptedStorage::new(InMemoryStorage::new(), [0u8; 32]);
+        let path = PathBuf::from("id_0");
+        storage.write(&path, b"abc").unwrap();
+        let raw = storage.inner.read(&path).unwrap();
+        assert_eq!(&raw[0..4], &MAGIC);
+        assert_eq!(raw[4], VERSION);
+        assert_eq!(raw.len(), HEADER_LEN + 3);
+    }
+}
@@ -0,0 +1,422 @@
+//! Crash-safe, append-only on-disk index for `OnDiskCorpus`, so a large
+//! corpus directory doesn't need to be listed and every seed re-read on
+//! startup.
+//!
+//! The index is a single binary file, `.afl_index` inside `dir_path`, laid out as a
+//! small "docket" header followed by one fixed-size `RawRecord` per
+//! testcase (flags, filename, input length). Records are only ever
+//! appended; after a record is written, the docket's `valid_len` is updated
+//! to include it. A crash mid-append leaves `valid_len` pointing at the
+//! last fully-written record, so a torn trailing write is simply ignored
+//! when the index is re-opened. Records are fixed-size so an entry can be
+//! located by its offset alone, without parsing everything before it.
+//!
+//! Parsing reads the whole file into a `Vec<u8>` and unpacks each record
+//! field-by-field with `from_le_bytes`, rather than mmap-ing and casting a
+//! raw pointer onto `RawRecord`/`RawDocket` — those structs' `u64` fields
+//! would need 8-byte alignment that a byte slice (mmap'd or heap-allocated)
+//! isn't guaranteed to have.
+
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::AflError;
+
+const MAGIC: [u8; 4] = *b"AFLI";
+const VERSION: u8 = 1;
+
+/// Name of the index file inside the corpus directory.
+pub(crate) const INDEX_FILENAME: &str = ".afl_index";
+
+/// Filenames (full paths) longer than this many bytes are not representable
+/// in the index: `append` rejects them, and `rebuild` leaves them out and
+/// counts them in `skipped`.
+pub const FILENAME_CAP: usize = 240;
+
+const PRESENT: u8 = 0b0000_0001;
+
+#[derive(Clone, Copy)]
+struct RawDocket {
+    magic: [u8; 4],
+    version: u8,
+    /// How many bytes after the docket are known-good records.
+    valid_len: u64,
+}
+
+const DOCKET_LEN: usize = 4 + 1 + 8;
+
+#[derive(Clone, Copy)]
+struct RawRecord {
+    flags: u8,
+    filename_len: u8,
+    input_len: u64,
+    filename: [u8; FILENAME_CAP],
+}
+
+const RECORD_LEN: usize = 1 + 1 + 8 + FILENAME_CAP;
+
+/// One parsed entry from the index: enough to reconstruct an unloaded
+/// `Testcase` (filename only, no input bytes read).
+pub struct IndexEntry {
+    pub filename: String,
+    pub input_len: u64,
+}
+
+fn record_from_filename(filename: &str, input_len: u64) -> Result<RawRecord, AflError> {
+    let bytes = filename.as_bytes();
+    if bytes.len() > FILENAME_CAP {
+        return Err(AflError::IllegalState(format!(
+            "Filename {:?} is longer than the index's {} byte cap",
+            filename, FILENAME_CAP
+        )));
+    }
+    let mut filename_buf = [0u8; FILENAME_CAP];
+    filename_buf[..bytes.len()].copy_from_slice(bytes);
+    Ok(RawRecord {
+        flags: PRESENT,
+        filename_len: bytes.len() as u8,
+        input_len,
+        filename: filename_buf,
+    })
+}
+
+fn record_to_entry(record: &RawRecord) -> Option<IndexEntry> {
+    if record.flags & PRESENT == 0 {
+        return None;
+    }
+    let len = record.filename_len as usize;
+    let filename = String::from_utf8_lossy(&record.filename[..len]).into_owned();
+    Some(IndexEntry {
+        filename,
+        input_len: record.input_len,
+    })
+}
+
+fn record_to_bytes(record: &RawRecord) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(RECORD_LEN);
+    bytes.push(record.flags);
+    bytes.push(record.filename_len);
+    bytes.extend_from_slice(&record.input_len.to_le_bytes());
+    bytes.extend_from_slice(&record.filename);
+    bytes
+}
+
+fn record_from_bytes(bytes: &[u8]) -> RawRecord {
+    debug_assert_eq!(bytes.len(), RECORD_LEN);
+    let mut input_len_bytes = [0u8; 8];
+    input_len_bytes.copy_from_slice(&bytes[2..10]);
+    let mut filename = [0u8; FILENAME_CAP];
+    filename.copy_from_slice(&bytes[10..10 + FILENAME_CAP]);
+    RawRecord {
+        flags: bytes[0],
+        filename_len: bytes[1],
+        input_len: u64::from_le_bytes(input_len_bytes),
+        filename,
+    }
+}
+
+fn docket_to_bytes(docket: &RawDocket) -> [u8; DOCKET_LEN] {
+    let mut bytes = [0u8; DOCKET_LEN];
+    bytes[0..4].copy_from_slice(&docket.magic);
+    bytes[4] = docket.version;
+    bytes[5..13].copy_from_slice(&docket.valid_len.to_le_bytes());
+    bytes
+}
+
+fn docket_from_bytes(bytes: &[u8]) -> RawDocket {
+    debug_assert_eq!(bytes.len(), DOCKET_LEN);
+    let mut magic = [0u8; 4];
+    magic.copy_from_slice(&bytes[0..4]);
+    let mut valid_len_bytes = [0u8; 8];
+    valid_len_bytes.copy_from_slice(&bytes[5..13]);
+    RawDocket {
+        magic,
+        version: bytes[4],
+        valid_len: u64::from_le_bytes(valid_len_bytes),
+    }
+}
+
+/// The on-disk index file for a single `OnDiskCorpus` directory.
+pub struct CorpusIndex {
+    path: PathBuf,
+    record_count: u64,
+    /// How many files `rebuild` found but couldn't represent in the index
+    /// (e.g. a path longer than `FILENAME_CAP`), and so left out of the
+    /// reconstructed corpus. Always 0 for an index from `open`.
+    skipped: u64,
+}
+
+impl CorpusIndex {
+    fn index_path(dir_path: &Path) -> PathBuf {
+        dir_path.join(INDEX_FILENAME)
+    }
+
+    /// Opens the index file in `dir_path` and parses every
+    /// known-good record.
+    ///
+    /// Returns `Err` if the index doesn't exist or its header is corrupt;
+    /// callers should fall back to `rebuild` in that case.
+    pub fn open(dir_path: &Path) -> Result<(Self, Vec<IndexEntry>), AflError> {
+        let path = Self::index_path(dir_path);
+        let bytes = read_whole_file(&path)?;
+        if bytes.len() < DOCKET_LEN {
+            return Err(AflError::IllegalState(format!(
+                "Index {:?} is smaller than its header",
+                path
+            )));
+        }
+
+        let docket = docket_from_bytes(&bytes[..DOCKET_LEN]);
+        if docket.magic != MAGIC {
+            return Err(AflError::IllegalState(format!(
+                "Index {:?} has a bad magic, refusing to trust it",
+                path
+            )));
+        }
+        if docket.version != VERSION {
+            return Err(AflError::IllegalState(format!(
+                "Index {:?} has unsupported version {}",
+                path, docket.version
+            )));
+        }
+
+        // A torn trailing write (crash mid-append) is ignored: only the
+        // prefix covered by `valid_len`, clamped to what's actually on
+        // disk, is trusted.
+        let available = (bytes.len() - DOCKET_LEN) as u64;
+        let valid_len = docket.valid_len.min(available);
+        let valid_records = (valid_len / RECORD_LEN as u64) as usize;
+
+        let mut entries = Vec::with_capacity(valid_records);
+        for i in 0..valid_records {
+            let start = DOCKET_LEN + i * RECORD_LEN;
+            let record = record_from_bytes(&bytes[start..start + RECORD_LEN]);
+            if let Some(entry) = record_to_entry(&record) {
+                entries.push(entry);
+            }
+        }
+
+        Ok((
+            Self {
+                path,
+                record_count: valid_records as u64,
+                skipped: 0,
+            },
+            entries,
+        ))
+    }
+
+    /// How many files `rebuild` couldn't represent in the index and so
+    /// silently left out, if this index came from `rebuild`.
+    pub fn skipped(&self) -> u64 {
+        self.skipped
+    }
+
+    /// Regenerates the index from scratch by listing `dir_path`, for when
+    /// the index is missing or failed to parse.
+    pub fn rebuild(dir_path: &Path) -> Result<(Self, Vec<IndexEntry>), AflError> {
+        let path = Self::index_path(dir_path);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| AflError::IllegalState(format!("Could not create {:?}: {:?}", path, e)))?;
+
+        let mut entries = Vec::new();
+        let dir = std::fs::read_dir(dir_path)
+            .map_err(|e| AflError::IllegalState(format!("Could not read dir {:?}: {:?}", dir_path, e)))?;
+        let mut records = Vec::new();
+        let mut skipped = 0u64;
+        for dir_entry in dir {
+            let dir_entry =
+                dir_entry.map_err(|e| AflError::IllegalState(format!("{:?}", e)))?;
+            let entry_path = dir_entry.path();
+            if !entry_path.is_file() || entry_path == path {
+                continue;
+            }
+            let filename = match entry_path.to_str() {
+                Some(f) => f.to_owned(),
+                None => {
+                    eprintln!(
+                        "CorpusIndex::rebuild: skipping {:?}, its path isn't valid UTF-8",
+                        entry_path
+                    );
+                    skipped += 1;
+                    continue;
+                }
+            };
+            let input_len = dir_entry.metadata().map(|m| m.len()).unwrap_or(0);
+            match record_from_filename(&filename, input_len) {
+                Ok(record) => {
+                    records.push(record);
+                    entries.push(IndexEntry { filename, input_len });
+                }
+                Err(err) => {
+                    eprintln!(
+                        "CorpusIndex::rebuild: skipping {:?}, it won't fit in the index: {:?}",
+                        filename, err
+                    );
+                    skipped += 1;
+                }
+            }
+        }
+
+        write_docket(&mut file, 0)?;
+        for record in &records {
+            append_record(&mut file, record)?;
+        }
+        write_docket(&mut file, records.len() as u64 * RECORD_LEN as u64)?;
+
+        Ok((
+            Self {
+                path,
+                record_count: records.len() as u64,
+                skipped,
+            },
+            entries,
+        ))
+    }
+
+    /// Appends a single record for a freshly-added testcase, then advances
+    /// the docket's `valid_len` to include it.
+    pub fn append(&mut self, filename: &str, input_len: u64) -> Result<(), AflError> {
+        let record = record_from_filename(filename, input_len)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .map_err(|e| AflError::IllegalState(format!("Could not open {:?}: {:?}", self.path, e)))?;
+        // Drop any torn trailing bytes past the last counted record, so the
+        // new record lands at the offset `valid_len` will say it is at.
+        file.set_len(DOCKET_LEN as u64 + self.record_count * RECORD_LEN as u64)
+            .map_err(|e| AflError::IllegalState(format!("{:?}", e)))?;
+        append_record(&mut file, &record)?;
+        self.record_count += 1;
+        write_docket(&mut file, self.record_count * RECORD_LEN as u64)?;
+        Ok(())
+    }
+}
+
+fn write_docket(file: &mut File, valid_len: u64) -> Result<(), AflError> {
+    let docket = RawDocket {
+        magic: MAGIC,
+        version: VERSION,
+        valid_len,
+    };
+    file.seek(SeekFrom::Start(0))
+        .map_err(|e| AflError::IllegalState(format!("{:?}", e)))?;
+    file.write_all(&docket_to_bytes(&docket))
+        .map_err(|e| AflError::IllegalState(format!("Could not write index header: {:?}", e)))?;
+    file.flush()
+        .map_err(|e| AflError::IllegalState(format!("{:?}", e)))
+}
+
+fn append_record(file: &mut File, record: &RawRecord) -> Result<(), AflError> {
+    file.seek(SeekFrom::End(0))
+        .map_err(|e| AflError::IllegalState(format!("{:?}", e)))?;
+    file.write_all(&record_to_bytes(record))
+        .map_err(|e| AflError::IllegalState(format!("Could not append index record: {:?}", e)))?;
+    file.flush()
+        .map_err(|e| AflError::IllegalState(format!("{:?}", e)))
+}
+
+fn read_whole_file(path: &Path) -> Result<Vec<u8>, AflError> {
+    let mut file = File::open(path)
+        .map_err(|e| AflError::IllegalState(format!("Could not open {:?}: {:?}", path, e)))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)
+        .map_err(|e| AflError::IllegalState(format!("Could not read {:?}: {:?}", path, e)))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        static COUNTER: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "libafl_index_test_{}_{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_rebuild_then_reopen() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("id_0"), b"aaaa").unwrap();
+        std::fs::write(dir.join("id_1"), b"bb").unwrap();
+
+        let (index, entries) = CorpusIndex::rebuild(&dir).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(index.skipped(), 0);
+
+        let (_, reopened) = CorpusIndex::open(&dir).unwrap();
+        assert_eq!(reopened.len(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_rebuild_skips_and_counts_oversized_filenames() {
+        let dir = temp_dir();
+        std::fs::write(dir.join("id_0"), b"aaaa").unwrap();
+        // A filename whose full path exceeds FILENAME_CAP can't be
+        // represented in a fixed-size record.
+        let long_name = "x".repeat(FILENAME_CAP + 1);
+        std::fs::write(dir.join(&long_name), b"bb").unwrap();
+
+        let (index, entries) = CorpusIndex::rebuild(&dir).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(index.skipped(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_append_is_visible_on_reopen() {
+        let dir = temp_dir();
+        let (mut index, entries) = CorpusIndex::rebuild(&dir).unwrap();
+        assert_eq!(entries.len(), 0);
+
+        let filename = dir.join("id_0").to_str().unwrap().to_owned();
+        std::fs::write(&filename, b"abcd").unwrap();
+        index.append(&filename, 4).unwrap();
+
+        let (_, reopened) = CorpusIndex::open(&dir).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened[0].filename, filename);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_torn_trailing_write_is_ignored() {
+        let dir = temp_dir();
+        let (mut index, _) = CorpusIndex::rebuild(&dir).unwrap();
+        let filename = dir.join("id_0").to_str().unwrap().to_owned();
+        std::fs::write(&filename, b"abcd").unwrap();
+        index.append(&filename, 4).unwrap();
+
+        // Simulate a crash mid-append: garbage bytes appended after the
+        // last complete, counted record.
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(&index.path)
+            .unwrap();
+        file.write_all(&[0xAA; 37]).unwrap();
+        drop(file);
+
+        let (_, reopened) = CorpusIndex::open(&dir).unwrap();
+        assert_eq!(reopened.len(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
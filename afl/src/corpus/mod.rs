@@ -1,7 +1,36 @@
 pub mod testcase;
 pub use testcase::{Testcase, TestcaseMetadata};
 
+pub mod scheduler;
+pub use scheduler::{PowerScheduler, RoundRobinScheduler, Scheduler, UniformScheduler};
+
+#[cfg(feature = "std")]
+pub mod storage;
+#[cfg(feature = "std")]
+pub use storage::{CorpusStorage, InMemoryStorage, StdFsStorage};
+
+#[cfg(feature = "std")]
+pub mod cache;
+#[cfg(feature = "std")]
+pub use cache::TestcaseCache;
+
+#[cfg(feature = "std")]
+pub mod encrypted;
+#[cfg(feature = "std")]
+pub use encrypted::EncryptedStorage;
+
+#[cfg(feature = "std")]
+pub mod sync;
+#[cfg(feature = "std")]
+pub use sync::SyncingCorpus;
+
+#[cfg(feature = "std")]
+mod index;
+#[cfg(feature = "std")]
+use index::CorpusIndex;
+
 use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 use core::marker::PhantomData;
 use core::ptr;
@@ -40,6 +69,16 @@ where
         self.entries_mut().push(testcase);
     }
 
+    /// Like `add`, but surfaces a failure to persist the entry instead of
+    /// only logging it (or, for a purely in-memory corpus, silently
+    /// succeeding either way). The default always succeeds, since the
+    /// default `add` has nothing that can fail; `OnDiskCorpus` overrides
+    /// this to report a failed disk write.
+    fn try_add(&mut self, testcase: Testcase<I>) -> Result<(), AflError> {
+        self.add(testcase);
+        Ok(())
+    }
+
     /// Replaces the testcase at the given idx
     fn replace(&mut self, idx: usize, testcase: Testcase<I>) -> Result<(), AflError> {
         if self.entries_mut().len() < idx {
@@ -76,26 +115,44 @@ where
         }
     }
 
-    /// Returns the testcase for the given idx, with loaded input
+    /// Samples an entry with probability proportional to its `weight`,
+    /// via a cumulative-sum scan over `entries`, so seeds that are more
+    /// valuable (e.g. smaller, or more likely to find new coverage) can be
+    /// fuzzed more often than a uniform `random_entry` would pick them.
+    fn weighted_entry(&self, rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
+        let entries = self.entries();
+        if entries.is_empty() {
+            return Err(AflError::Empty("No entries in corpus".to_owned()));
+        }
+        let total: f64 = entries.iter().map(Testcase::weight).sum();
+        if total <= 0.0 {
+            return self.random_entry(rand);
+        }
+        let sample = (rand.below(1_000_000) as f64 / 1_000_000.0) * total;
+        let mut acc = 0.0;
+        for (idx, testcase) in entries.iter().enumerate() {
+            acc += testcase.weight();
+            if sample < acc {
+                return Ok((self.get(idx), idx));
+            }
+        }
+        let last = entries.len() - 1;
+        Ok((self.get(last), last))
+    }
+
+    /// Returns the testcase for the given idx, with loaded input.
+    ///
+    /// The default implementation has no backing storage to load from, so it
+    /// only succeeds for testcases that are already loaded; `Corpus` impls
+    /// backed by a `CorpusStorage` (like `OnDiskCorpus`) override this.
     fn load_testcase(&mut self, idx: usize) -> Result<(), AflError> {
         let testcase = self.get(idx);
-        // Ensure testcase is loaded
         match testcase.input() {
-            None => {
-                let new_testcase = match testcase.filename() {
-                    Some(filename) => Testcase::load_from_disk(filename)?,
-                    None => {
-                        return Err(AflError::IllegalState(
-                            "Neither input, nor filename specified for testcase".into(),
-                        ))
-                    }
-                };
-
-                self.replace(idx, new_testcase)?;
-            }
-            _ => (),
+            None => Err(AflError::IllegalState(
+                "This corpus has no backing storage to load testcases from".into(),
+            )),
+            _ => Ok(()),
         }
-        Ok(())
     }
 
     // TODO: IntoIter
@@ -113,7 +170,7 @@ where
 {
     entries: Vec<Testcase<I>>,
     pos: usize,
-    phantom: PhantomData<R>,
+    scheduler: Box<dyn Scheduler<I, R>>,
 }
 
 impl<I, R> HasTestcaseVec<I> for InMemoryCorpus<I, R>
@@ -134,22 +191,28 @@ where
     I: Input,
     R: Rand,
 {
-    /// Gets the next entry
+    /// Gets the next entry, as picked by this corpus's `Scheduler` (uniform
+    /// at random by default).
     fn next(&mut self, rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
-        if self.count() == 0 {
-            Err(AflError::Empty("No entries in corpus".to_owned()))
-        } else {
-            let len = { self.entries().len() };
-            let id = rand.below(len as u64) as usize;
-            self.pos = id;
-            Ok((self.get(id), id))
-        }
+        let id = self.scheduler.next(&self.entries, rand)?;
+        self.pos = id;
+        Ok((self.get(id), id))
     }
 
     /// Returns the testacase we currently use
     fn current_testcase(&self) -> (&Testcase<I>, usize) {
         (self.get(self.pos), self.pos)
     }
+
+    /// Removes an entry, then tells the scheduler so index-keyed state
+    /// (e.g. `PowerScheduler`'s boosts) gets renumbered to match the vec
+    /// shifting every later index down by one.
+    fn remove(&mut self, entry: &Testcase<I>) -> Option<Testcase<I>> {
+        let idx = self.entries.iter().position(|x| ptr::eq(x, entry))?;
+        let removed = self.entries.remove(idx);
+        self.scheduler.on_removed(idx);
+        Some(removed)
+    }
 }
 
 impl<I, R> InMemoryCorpus<I, R>
@@ -161,28 +224,48 @@ where
         Self {
             entries: vec![],
             pos: 0,
-            phantom: PhantomData,
+            scheduler: Box::new(UniformScheduler::new()),
         }
     }
+
+    /// Swaps in a different scheduling strategy (round-robin, weighted/
+    /// power-schedule, or a custom one) without changing any harness code.
+    pub fn set_scheduler(&mut self, scheduler: Box<dyn Scheduler<I, R>>) {
+        self.scheduler = scheduler;
+    }
+
+    /// Reports how `idx` fared after being fuzzed, so a scheduler like
+    /// `PowerScheduler` can boost or decay its future selection odds.
+    pub fn notify_evaluated(&mut self, idx: usize, found_new_coverage: bool) {
+        self.scheduler.on_evaluated(idx, found_new_coverage);
+    }
 }
 
 #[cfg(feature = "std")]
-pub struct OnDiskCorpus<I, R>
+pub struct OnDiskCorpus<I, R, S = StdFsStorage>
 where
     I: Input,
     R: Rand,
+    S: CorpusStorage,
 {
     entries: Vec<Testcase<I>>,
     dir_path: PathBuf,
+    storage: S,
+    cache: TestcaseCache,
+    /// The on-disk index, when this corpus was constructed via `open` (or
+    /// found/rebuilt one); `None` means entries are tracked purely in memory,
+    /// as before the index existed.
+    index: Option<CorpusIndex>,
     pos: usize,
     phantom: PhantomData<R>,
 }
 
 #[cfg(feature = "std")]
-impl<I, R> HasTestcaseVec<I> for OnDiskCorpus<I, R>
+impl<I, R, S> HasTestcaseVec<I> for OnDiskCorpus<I, R, S>
 where
     I: Input,
     R: Rand,
+    S: CorpusStorage,
 {
     fn entries(&self) -> &[Testcase<I>] {
         &self.entries
@@ -193,23 +276,80 @@ where
 }
 
 #[cfg(feature = "std")]
-impl<I, R> Corpus<I, R> for OnDiskCorpus<I, R>
+impl<I, R, S> Corpus<I, R> for OnDiskCorpus<I, R, S>
 where
     I: Input,
     R: Rand,
+    S: CorpusStorage,
 {
-    /// Add an entry and save it to disk
-    fn add(&mut self, mut entry: Testcase<I>) {
-        match entry.filename() {
-            None => {
-                // TODO walk entry metadatas to ask for pices of filename (e.g. :havoc in AFL)
-                let filename = self.dir_path.join(format!("id_{}", &self.entries.len()));
-                let filename_str = filename.to_str().expect("Invalid Path");
-                entry.set_filename(filename_str.into());
+    /// Add an entry and save it to disk. Since `Corpus::add` can't report a
+    /// failure, this falls back to logging it; callers that want the error
+    /// should use `try_add` instead.
+    fn add(&mut self, entry: Testcase<I>) {
+        if let Err(err) = self.try_add(entry) {
+            eprintln!("OnDiskCorpus::add: {:?}", err);
+        }
+    }
+
+    /// Add an entry and save it to disk, returning the first storage error
+    /// encountered instead of discarding it.
+    ///
+    /// Best-effort: the testcase is kept in the in-memory vec even if the
+    /// write fails, mirroring how `replace`/`remove` don't roll back the vec
+    /// on storage errors either — only the `Err` return differs from `add`.
+    fn try_add(&mut self, mut entry: Testcase<I>) -> Result<(), AflError> {
+        if entry.filename().is_none() {
+            // TODO walk entry metadatas to ask for pices of filename (e.g. :havoc in AFL)
+            let filename = self.dir_path.join(format!("id_{}", &self.entries.len()));
+            let filename_str = filename.to_str().expect("Invalid Path");
+            entry.set_filename(filename_str.into());
+        }
+        let mut result = Ok(());
+        if let Err(err) = entry.save_to_disk(&self.storage) {
+            result = Err(AflError::IllegalState(format!(
+                "failed to persist {:?}: {:?}",
+                entry.filename(),
+                err
+            )));
+        }
+        if let (Some(index), Some(filename), Some(input)) =
+            (self.index.as_mut(), entry.filename(), entry.input())
+        {
+            if let Err(err) = index.append(filename, input.to_bytes().len() as u64) {
+                if result.is_ok() {
+                    result = Err(AflError::IllegalState(format!(
+                        "failed to append {:?} to the index: {:?}",
+                        filename, err
+                    )));
+                }
             }
-            _ => {}
         }
+        let idx = self.entries.len();
         self.entries.push(entry);
+        self.cache.touch(idx);
+        self.evict_if_over_budget();
+        result
+    }
+
+    /// Removes an entry, then renumbers the cache's recency list to match
+    /// the vec shifting every later index down by one; the default
+    /// `Corpus::remove` would otherwise leave the cache's indices stale.
+    fn remove(&mut self, entry: &Testcase<I>) -> Option<Testcase<I>> {
+        let idx = self.entries.iter().position(|x| ptr::eq(x, entry))?;
+        let removed = self.entries.remove(idx);
+        self.cache.remove_and_reindex(idx);
+        Some(removed)
+    }
+
+    fn get(&self, idx: usize) -> &Testcase<I> {
+        let entry = &self.entries()[idx];
+        // Only a loaded testcase occupies a slot in the bounded working set;
+        // merely peeking at an unloaded one (e.g. a scheduler scan) must not
+        // perturb recency or trigger eviction of something else.
+        if entry.input().is_some() {
+            self.cache.touch(idx);
+        }
+        entry
     }
 
     fn current_testcase(&self) -> (&Testcase<I>, usize) {
@@ -228,23 +368,141 @@ where
         }
     }
 
-    // TODO save and remove files, cache, etc..., ATM use just InMemoryCorpus
+    /// Returns the testcase for the given idx, loading it from storage if
+    /// needed, then evicts the least-recently-used loaded testcase if doing
+    /// so pushed the working set over `max_loaded`.
+    fn load_testcase(&mut self, idx: usize) -> Result<(), AflError> {
+        let testcase = self.get(idx);
+        match testcase.input() {
+            None => {
+                let new_testcase = match testcase.filename() {
+                    Some(filename) => Testcase::load_from_disk(&self.storage, filename)?,
+                    None => {
+                        return Err(AflError::IllegalState(
+                            "Neither input, nor filename specified for testcase".into(),
+                        ))
+                    }
+                };
+                self.replace(idx, new_testcase)?;
+            }
+            _ => (),
+        }
+        self.cache.touch(idx);
+        self.evict_if_over_budget();
+        Ok(())
+    }
 }
 
 #[cfg(feature = "std")]
-impl<I, R> OnDiskCorpus<I, R>
+impl<I, R> OnDiskCorpus<I, R, StdFsStorage>
 where
     I: Input,
     R: Rand,
 {
+    /// Creates a new `OnDiskCorpus`, persisting testcases directly to `dir_path`
+    /// via `std::fs`.
+    ///
+    /// Also attaches a fresh on-disk index (the same one `open` would
+    /// reconstruct), so a campaign started the normal way gets the
+    /// avoid-rescanning-on-restart benefit from the start, not only when
+    /// explicitly constructed via `open`. If `dir_path` doesn't exist yet,
+    /// indexing is best-effort: this silently falls back to no index (as
+    /// before this existed), since there's nothing to index yet and the
+    /// caller may create the directory before the first `add`.
     pub fn new(dir_path: PathBuf) -> Self {
+        let storage = StdFsStorage::new(dir_path.clone());
+        let mut corpus = Self::with_storage(dir_path.clone(), storage);
+        if let Ok((index, _)) = CorpusIndex::rebuild(&dir_path) {
+            corpus.index = Some(index);
+        }
+        corpus
+    }
+
+    /// Reconstructs a corpus from `dir_path`'s on-disk index instead of
+    /// listing and loading every testcase. Entries come back with filenames
+    /// only (inputs unloaded); use `load_testcase` to pull one in on demand.
+    ///
+    /// Falls back to `rebuild`, regenerating the index from a directory
+    /// listing, if the index is missing or fails to parse.
+    pub fn open(dir_path: PathBuf) -> Result<Self, AflError> {
+        let (index, parsed) = match CorpusIndex::open(&dir_path) {
+            Ok(opened) => opened,
+            Err(_) => CorpusIndex::rebuild(&dir_path)?,
+        };
+        if index.skipped() > 0 {
+            eprintln!(
+                "OnDiskCorpus::open: {:?} left {} file(s) out of the rebuilt index, see prior warnings",
+                dir_path,
+                index.skipped()
+            );
+        }
+
+        let storage = StdFsStorage::new(dir_path.clone());
+        let mut corpus = Self::with_storage(dir_path, storage);
+        // Entries come back unloaded (filenames only), so none of them
+        // occupy the in-memory cache's working set yet.
+        corpus.entries = parsed
+            .into_iter()
+            .map(|entry| Testcase::unloaded(entry.filename))
+            .collect();
+        corpus.index = Some(index);
+        Ok(corpus)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I, R, S> OnDiskCorpus<I, R, S>
+where
+    I: Input,
+    R: Rand,
+    S: CorpusStorage,
+{
+    /// Creates a new `OnDiskCorpus` backed by a custom `CorpusStorage`, so
+    /// alternative targets (a ramdisk, an object store, an in-memory store
+    /// for tests) can be plugged in without rewriting the `Corpus` impl.
+    pub fn with_storage(dir_path: PathBuf, storage: S) -> Self {
         Self {
-            dir_path: dir_path,
+            dir_path,
+            storage,
+            // Unbounded by default, so behavior is unchanged until a caller
+            // opts in via `set_max_loaded`.
+            cache: TestcaseCache::new(usize::max_value()),
+            index: None,
             entries: vec![],
             pos: 0,
             phantom: PhantomData,
         }
     }
+
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Caps how many testcases may be loaded in memory at once, evicting the
+    /// least-recently-used ones (writing them back to disk first if dirty)
+    /// once the cap is exceeded.
+    pub fn set_max_loaded(&mut self, max_loaded: usize) {
+        self.cache.set_max_loaded(max_loaded);
+        self.evict_if_over_budget();
+    }
+
+    /// How many testcases are currently loaded in memory.
+    pub fn loaded_count(&self) -> usize {
+        self.cache.loaded_count()
+    }
+
+    fn evict_if_over_budget(&mut self) {
+        while let Some(idx) = self.cache.evict_candidate() {
+            let entry = &mut self.entries[idx];
+            if entry.is_dirty() && entry.save_to_disk(&self.storage).is_err() {
+                // Best-effort: keep the input loaded rather than silently
+                // dropping unsaved data if the write-back failed.
+                self.cache.touch(idx);
+                break;
+            }
+            self.entries[idx].unload();
+        }
+    }
 }
 
 /// A Queue-like corpus, wrapping an existing Corpus instance
@@ -257,6 +515,10 @@ where
     corpus: C,
     pos: usize,
     cycles: u64,
+    /// When set, `next` delegates selection to this scheduler instead of
+    /// plain sequential round-robin, so callers can opt into weighted or
+    /// power-schedule-style selection without changing harness code.
+    scheduler: Option<Box<dyn Scheduler<I, R>>>,
     phantom: PhantomData<(I, R)>,
 }
 
@@ -290,8 +552,20 @@ where
     }
 
     /// Removes an entry from the corpus, returning it if it was present.
+    ///
+    /// Looks up the index before delegating so that, if this `QueueCorpus`
+    /// has its own `scheduler` set, it can be told about the removal too —
+    /// the wrapped corpus reindexing its own state isn't enough when the
+    /// `QueueCorpus` keeps independent index-keyed scheduler state on top.
     fn remove(&mut self, entry: &Testcase<I>) -> Option<Testcase<I>> {
-        self.corpus.remove(entry)
+        let idx = self.corpus.entries().iter().position(|x| ptr::eq(x, entry));
+        let removed = self.corpus.remove(entry);
+        if removed.is_some() {
+            if let (Some(idx), Some(scheduler)) = (idx, &mut self.scheduler) {
+                scheduler.on_removed(idx);
+            }
+        }
+        removed
     }
 
     /// Gets a random entry
@@ -299,23 +573,38 @@ where
         self.corpus.random_entry(rand)
     }
 
+    /// Loads the testcase at `idx`, delegating to the wrapped corpus since
+    /// it, not `QueueCorpus` itself, knows how to reach backing storage.
+    fn load_testcase(&mut self, idx: usize) -> Result<(), AflError> {
+        self.corpus.load_testcase(idx)
+    }
+
     /// Returns the testacase we currently use
     fn current_testcase(&self) -> (&Testcase<I>, usize) {
         (self.get(self.pos - 1), self.pos - 1)
     }
 
-    /// Gets the next entry
-    fn next(&mut self, _rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
-        self.pos += 1;
+    /// Gets the next entry: plain sequential round-robin by default, or
+    /// whatever `self.scheduler` picks if one has been set via
+    /// `set_scheduler`.
+    fn next(&mut self, rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
         if self.corpus.count() == 0 {
             return Err(AflError::Empty("Corpus".to_owned()));
         }
-        if self.pos > self.corpus.count() {
-            // TODO: Always loop or return informational error?
-            self.pos = 1;
-            self.cycles += 1;
-        }
-        Ok((&self.corpus.entries()[self.pos - 1], self.pos - 1))
+        let idx = match &mut self.scheduler {
+            Some(scheduler) => scheduler.next(self.corpus.entries(), rand)?,
+            None => {
+                self.pos += 1;
+                if self.pos > self.corpus.count() {
+                    // TODO: Always loop or return informational error?
+                    self.pos = 1;
+                    self.cycles += 1;
+                }
+                self.pos - 1
+            }
+        };
+        self.pos = idx + 1;
+        Ok((&self.corpus.entries()[idx], idx))
     }
 }
 
@@ -331,6 +620,7 @@ where
             phantom: PhantomData,
             cycles: 0,
             pos: 0,
+            scheduler: None,
         }
     }
 
@@ -341,6 +631,21 @@ where
     pub fn pos(&self) -> usize {
         self.pos
     }
+
+    /// Swaps in a different scheduling strategy (round-robin, uniform,
+    /// weighted/power-schedule, or a custom one) for `next`. Pass `None` to
+    /// go back to plain sequential round-robin.
+    pub fn set_scheduler(&mut self, scheduler: Option<Box<dyn Scheduler<I, R>>>) {
+        self.scheduler = scheduler;
+    }
+
+    /// Reports how `idx` fared after being fuzzed, so a scheduler like
+    /// `PowerScheduler` can boost or decay its future selection odds.
+    pub fn notify_evaluated(&mut self, idx: usize, found_new_coverage: bool) {
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.on_evaluated(idx, found_new_coverage);
+        }
+    }
 }
 
 /* TODO: Iterator corpus, like:
@@ -396,6 +701,7 @@ And then:
 #[cfg(feature = "std")]
 mod tests {
     use crate::corpus::Corpus;
+    use crate::corpus::HasTestcaseVec;
     use crate::corpus::Testcase;
     use crate::corpus::{OnDiskCorpus, QueueCorpus};
     use crate::inputs::bytes::BytesInput;
@@ -431,4 +737,212 @@ mod tests {
         );
         assert_eq!(filename, "fancyfile");
     }
+
+    #[test]
+    fn test_ondiskcorpus_in_memory_storage() {
+        let storage = crate::corpus::InMemoryStorage::new();
+        let mut corpus = OnDiskCorpus::<BytesInput, StdRand, _>::with_storage(
+            PathBuf::from("fancy/path"),
+            storage,
+        );
+        corpus.add(Testcase::new(BytesInput::new(vec![1, 2, 3, 4])));
+        // Drop the in-memory input to force a reload through the storage backend.
+        corpus.entries_mut()[0].unload();
+        corpus.load_testcase(0).unwrap();
+        assert!(corpus.get(0).input().is_some());
+    }
+
+    #[test]
+    fn test_ondiskcorpus_evicts_over_max_loaded() {
+        let storage = crate::corpus::InMemoryStorage::new();
+        let mut corpus = OnDiskCorpus::<BytesInput, StdRand, _>::with_storage(
+            PathBuf::from("fancy/path"),
+            storage,
+        );
+        corpus.set_max_loaded(1);
+        corpus.add(Testcase::new(BytesInput::new(vec![1])));
+        corpus.add(Testcase::new(BytesInput::new(vec![2])));
+        // Adding the second testcase should have evicted the first.
+        assert_eq!(corpus.loaded_count(), 1);
+        assert!(corpus.entries()[0].input().is_none());
+        assert!(corpus.entries()[1].input().is_some());
+
+        // Touching the evicted one reloads it, and evicts the other.
+        corpus.load_testcase(0).unwrap();
+        assert_eq!(corpus.loaded_count(), 1);
+        assert!(corpus.entries()[0].input().is_some());
+        assert!(corpus.entries()[1].input().is_none());
+    }
+
+    #[test]
+    fn test_ondiskcorpus_try_add_surfaces_persist_failure() {
+        // A `dir_path` that doesn't exist makes the underlying write fail;
+        // `try_add` must report that instead of silently discarding it, while
+        // `add` keeps the best-effort, log-and-continue behavior.
+        let dir = PathBuf::from("/nonexistent/libafl_try_add_test_dir");
+        let mut corpus = OnDiskCorpus::<BytesInput, StdRand>::new(dir);
+
+        let err = corpus
+            .try_add(Testcase::new(BytesInput::new(vec![1, 2, 3])))
+            .unwrap_err();
+        assert!(format!("{:?}", err).contains("persist"));
+        // Best-effort: the testcase is still kept in memory despite the
+        // failed write.
+        assert_eq!(corpus.count(), 1);
+
+        corpus.add(Testcase::new(BytesInput::new(vec![4, 5, 6])));
+        assert_eq!(corpus.count(), 2);
+    }
+
+    #[test]
+    fn test_ondiskcorpus_remove_reindexes_cache() {
+        let storage = crate::corpus::InMemoryStorage::new();
+        let mut corpus = OnDiskCorpus::<BytesInput, StdRand, _>::with_storage(
+            PathBuf::from("fancy/path"),
+            storage,
+        );
+        corpus.set_max_loaded(2);
+        corpus.add(Testcase::new(BytesInput::new(vec![0])));
+        corpus.add(Testcase::new(BytesInput::new(vec![1])));
+        corpus.add(Testcase::new(BytesInput::new(vec![2])));
+
+        // Remove the middle entry by identity, the way `Corpus::remove`
+        // expects; index 2 shifts down to 1 both in the vec and in the
+        // cache's recency bookkeeping. The pointer is only read back before
+        // `remove` shifts the vec, so this doesn't outlive the entry it
+        // points at.
+        let entry_ptr: *const Testcase<BytesInput> = &corpus.entries()[1];
+        let removed = corpus.remove(unsafe { &*entry_ptr }).unwrap();
+        assert!(removed.input().is_some());
+        assert_eq!(corpus.count(), 2);
+
+        // Loading another testcase must not panic or evict based on a stale
+        // (pre-removal) index.
+        corpus.add(Testcase::new(BytesInput::new(vec![3])));
+        assert!(corpus.loaded_count() <= 2);
+    }
+
+    #[test]
+    fn test_ondiskcorpus_open_reconstructs_from_index() {
+        let dir = std::env::temp_dir().join(format!(
+            "libafl_ondiskcorpus_open_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        {
+            let mut corpus = OnDiskCorpus::<BytesInput, StdRand>::new(dir.clone());
+            corpus.add(Testcase::new(BytesInput::new(vec![1, 2, 3])));
+            corpus.add(Testcase::new(BytesInput::new(vec![4, 5])));
+            // `new` already attached and maintained an index here; delete it
+            // to exercise `open`'s rebuild-from-directory-listing fallback,
+            // the way a campaign would recover from a missing/corrupt index.
+            let _ = std::fs::remove_file(dir.join(".afl_index"));
+        }
+
+        let corpus = OnDiskCorpus::<BytesInput, StdRand>::open(dir.clone()).unwrap();
+        assert_eq!(corpus.count(), 2);
+        assert!(corpus.entries()[0].input().is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_ondiskcorpus_new_already_maintains_an_index() {
+        let dir = std::env::temp_dir().join(format!(
+            "libafl_ondiskcorpus_new_index_test_{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        {
+            let mut corpus = OnDiskCorpus::<BytesInput, StdRand>::new(dir.clone());
+            corpus.add(Testcase::new(BytesInput::new(vec![1, 2, 3])));
+            corpus.add(Testcase::new(BytesInput::new(vec![4, 5])));
+        }
+
+        // The index file is there without ever having gone through `open`,
+        // so a restart can reconstruct the corpus from it directly rather
+        // than falling back to a directory listing.
+        assert!(dir.join(".afl_index").exists());
+        let corpus = OnDiskCorpus::<BytesInput, StdRand>::open(dir.clone()).unwrap();
+        assert_eq!(corpus.count(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_weighted_entry_favors_higher_weight() {
+        let mut rand = StdRand::new(0);
+        let mut corpus = crate::corpus::InMemoryCorpus::<BytesInput, StdRand>::new();
+        corpus.add(Testcase::new(BytesInput::new(vec![0])));
+        corpus.add(Testcase::new(BytesInput::new(vec![1])));
+        corpus.entries_mut()[0].set_weight(0.0);
+        corpus.entries_mut()[1].set_weight(1.0);
+        for _ in 0..20 {
+            let (_, idx) = corpus.weighted_entry(&mut rand).unwrap();
+            assert_eq!(idx, 1);
+        }
+    }
+
+    #[test]
+    fn test_inmemorycorpus_remove_notifies_scheduler_with_correct_index() {
+        // `PowerScheduler::on_removed` is exercised directly in
+        // scheduler.rs; this checks the wiring, i.e. that
+        // `InMemoryCorpus::remove` actually calls `on_removed` with the
+        // removed-from index rather than some other one, and that the
+        // corpus itself stays usable afterwards.
+        use crate::corpus::Scheduler;
+        use crate::utils::Rand;
+        use alloc::rc::Rc;
+        use core::cell::RefCell;
+
+        struct RecordingScheduler(Rc<RefCell<alloc::vec::Vec<usize>>>);
+        impl Scheduler<BytesInput, StdRand> for RecordingScheduler {
+            fn next(
+                &mut self,
+                entries: &[Testcase<BytesInput>],
+                rand: &mut StdRand,
+            ) -> Result<usize, crate::AflError> {
+                Ok(rand.below(entries.len() as u64) as usize)
+            }
+            fn on_removed(&mut self, idx: usize) {
+                self.0.borrow_mut().push(idx);
+            }
+        }
+
+        let removed_indices = Rc::new(RefCell::new(alloc::vec::Vec::new()));
+        let mut corpus = crate::corpus::InMemoryCorpus::<BytesInput, StdRand>::new();
+        corpus.add(Testcase::new(BytesInput::new(vec![0])));
+        corpus.add(Testcase::new(BytesInput::new(vec![1])));
+        corpus.add(Testcase::new(BytesInput::new(vec![2])));
+        corpus.set_scheduler(alloc::boxed::Box::new(RecordingScheduler(
+            removed_indices.clone(),
+        )));
+        corpus.notify_evaluated(2, true);
+
+        let entry_ptr: *const Testcase<BytesInput> = &corpus.entries()[1];
+        let removed = corpus.remove(unsafe { &*entry_ptr }).unwrap();
+        assert!(removed.input().is_some());
+        assert_eq!(corpus.count(), 2);
+        assert_eq!(*removed_indices.borrow(), alloc::vec![1]);
+
+        let mut rand = StdRand::new(0);
+        let (_, idx) = corpus.next(&mut rand).unwrap();
+        assert!(idx < corpus.count());
+    }
+
+    #[test]
+    fn test_queuecorpus_round_robin_scheduler_matches_default() {
+        let mut rand = StdRand::new(0);
+        let mut corpus = crate::corpus::InMemoryCorpus::<BytesInput, StdRand>::new();
+        corpus.add(Testcase::new(BytesInput::new(vec![0])));
+        corpus.add(Testcase::new(BytesInput::new(vec![1])));
+        let mut q = QueueCorpus::new(corpus);
+        q.set_scheduler(Some(alloc::boxed::Box::new(
+            crate::corpus::RoundRobinScheduler::new(),
+        )));
+        let picks: alloc::vec::Vec<usize> = (0..3).map(|_| q.next(&mut rand).unwrap().1).collect();
+        assert_eq!(picks, alloc::vec![0, 1, 0]);
+    }
 }
@@ -0,0 +1,216 @@
+//! Pluggable scheduling strategies for how `Corpus::next` picks the next
+//! testcase, so harness code can switch between round-robin, uniform, and
+//! weighted/energy-based selection without touching the `Corpus` impl.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::corpus::Testcase;
+use crate::inputs::Input;
+use crate::utils::Rand;
+use crate::AflError;
+
+/// An object a `Corpus` consults in `next` to pick the next testcase index.
+pub trait Scheduler<I, R>
+where
+    I: Input,
+    R: Rand,
+{
+    /// Picks the next index to fuzz out of `entries`.
+    fn next(&mut self, entries: &[Testcase<I>], rand: &mut R) -> Result<usize, AflError>;
+
+    /// Called after `idx` has finished being fuzzed, so schedulers that
+    /// reward productive seeds can update their bookkeeping.
+    fn on_evaluated(&mut self, _idx: usize, _found_new_coverage: bool) {}
+
+    /// Called after the testcase at `idx` has been removed from the corpus,
+    /// which shifts every later index down by one, so schedulers keyed by
+    /// index can renumber their bookkeeping to match.
+    fn on_removed(&mut self, _idx: usize) {}
+}
+
+/// Cycles through every entry in order, wrapping back to the start.
+pub struct RoundRobinScheduler {
+    pos: usize,
+}
+
+impl RoundRobinScheduler {
+    pub fn new() -> Self {
+        Self { pos: 0 }
+    }
+}
+
+impl<I, R> Scheduler<I, R> for RoundRobinScheduler
+where
+    I: Input,
+    R: Rand,
+{
+    fn next(&mut self, entries: &[Testcase<I>], _rand: &mut R) -> Result<usize, AflError> {
+        if entries.is_empty() {
+            return Err(AflError::Empty("No entries in corpus".into()));
+        }
+        let idx = self.pos % entries.len();
+        self.pos += 1;
+        Ok(idx)
+    }
+}
+
+/// Picks uniformly at random among all entries.
+pub struct UniformScheduler;
+
+impl UniformScheduler {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<I, R> Scheduler<I, R> for UniformScheduler
+where
+    I: Input,
+    R: Rand,
+{
+    fn next(&mut self, entries: &[Testcase<I>], rand: &mut R) -> Result<usize, AflError> {
+        if entries.is_empty() {
+            return Err(AflError::Empty("No entries in corpus".into()));
+        }
+        Ok(rand.below(entries.len() as u64) as usize)
+    }
+}
+
+/// Samples proportional to each entry's `weight`, the way `weighted_entry`
+/// on `Corpus` does, but additionally boosts seeds that recently produced
+/// new coverage and decays that boost back down otherwise, so productive
+/// seeds get fuzzed more than their static weight alone would suggest.
+pub struct PowerScheduler {
+    boost: BTreeMap<usize, f64>,
+    boost_factor: f64,
+    decay: f64,
+}
+
+impl PowerScheduler {
+    pub fn new() -> Self {
+        Self {
+            boost: BTreeMap::new(),
+            boost_factor: 4.0,
+            decay: 0.9,
+        }
+    }
+
+    fn weight_of<I: Input>(&self, idx: usize, testcase: &Testcase<I>) -> f64 {
+        testcase.weight() * self.boost.get(&idx).copied().unwrap_or(1.0)
+    }
+}
+
+impl<I, R> Scheduler<I, R> for PowerScheduler
+where
+    I: Input,
+    R: Rand,
+{
+    fn next(&mut self, entries: &[Testcase<I>], rand: &mut R) -> Result<usize, AflError> {
+        if entries.is_empty() {
+            return Err(AflError::Empty("No entries in corpus".into()));
+        }
+
+        let weights: Vec<f64> = entries
+            .iter()
+            .enumerate()
+            .map(|(idx, t)| self.weight_of(idx, t))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Ok(rand.below(entries.len() as u64) as usize);
+        }
+
+        let sample = (rand.below(1_000_000) as f64 / 1_000_000.0) * total;
+        let mut acc = 0.0;
+        for (idx, weight) in weights.iter().enumerate() {
+            acc += weight;
+            if sample < acc {
+                return Ok(idx);
+            }
+        }
+        Ok(entries.len() - 1)
+    }
+
+    fn on_evaluated(&mut self, idx: usize, found_new_coverage: bool) {
+        // Decay every boost toward 1.0 so being productive once isn't
+        // rewarded forever.
+        for boost in self.boost.values_mut() {
+            *boost = 1.0 + (*boost - 1.0) * self.decay;
+        }
+        if found_new_coverage {
+            self.boost.insert(idx, self.boost_factor);
+        }
+    }
+
+    fn on_removed(&mut self, idx: usize) {
+        self.boost = self
+            .boost
+            .iter()
+            .filter_map(|(&boosted_idx, &boost)| {
+                if boosted_idx < idx {
+                    Some((boosted_idx, boost))
+                } else if boosted_idx > idx {
+                    Some((boosted_idx - 1, boost))
+                } else {
+                    None
+                }
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::bytes::BytesInput;
+    use crate::utils::StdRand;
+
+    #[test]
+    fn test_round_robin_wraps() {
+        let mut scheduler = RoundRobinScheduler::new();
+        let mut rand = StdRand::new(0);
+        let entries: Vec<Testcase<BytesInput>> = (0..3)
+            .map(|_| Testcase::new(BytesInput::new(vec![0])))
+            .collect();
+        let picks: Vec<usize> = (0..4)
+            .map(|_| Scheduler::<BytesInput, StdRand>::next(&mut scheduler, &entries, &mut rand).unwrap())
+            .collect();
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_power_scheduler_never_picks_zero_weight_unless_forced() {
+        let mut scheduler = PowerScheduler::new();
+        let mut rand = StdRand::new(1337);
+        let mut entries: Vec<Testcase<BytesInput>> = (0..2)
+            .map(|_| Testcase::new(BytesInput::new(vec![0])))
+            .collect();
+        entries[0].set_weight(0.0);
+        entries[1].set_weight(1.0);
+        for _ in 0..20 {
+            let idx = Scheduler::<BytesInput, StdRand>::next(&mut scheduler, &entries, &mut rand).unwrap();
+            assert_eq!(idx, 1);
+        }
+    }
+
+    #[test]
+    fn test_power_scheduler_boosts_after_new_coverage() {
+        let mut scheduler = PowerScheduler::new();
+        scheduler.on_evaluated(0, true);
+        assert!(scheduler.boost.get(&0).copied().unwrap_or(1.0) > 1.0);
+    }
+
+    #[test]
+    fn test_power_scheduler_on_removed_renumbers_boosts() {
+        let mut scheduler = PowerScheduler::new();
+        scheduler.on_evaluated(0, true);
+        scheduler.on_evaluated(2, true);
+        // Removing index 1 (unboosted) must not disturb 0, but must shift
+        // the boost on 2 down to 1.
+        scheduler.on_removed(1);
+        assert!(scheduler.boost.get(&0).copied().unwrap_or(1.0) > 1.0);
+        assert!(scheduler.boost.get(&1).copied().unwrap_or(1.0) > 1.0);
+        assert!(scheduler.boost.get(&2).is_none());
+    }
+}
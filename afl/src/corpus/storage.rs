@@ -0,0 +1,146 @@
+//! Pluggable persistence backends for `OnDiskCorpus`, so the corpus itself
+//! does not need to know whether testcases end up on a real filesystem,
+//! in memory, or somewhere else entirely.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::AflError;
+
+/// A storage backend able to persist and retrieve testcase bytes by path.
+///
+/// Implementors back `OnDiskCorpus`, so alternative targets (a ramdisk, an
+/// object store, an in-memory store for tests) can be plugged in without
+/// touching the `Corpus` impl itself.
+pub trait CorpusStorage {
+    /// Reads the full contents stored at `path`.
+    fn read(&self, path: &Path) -> Result<Vec<u8>, AflError>;
+
+    /// Writes `data` to `path`, creating or overwriting it.
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), AflError>;
+
+    /// Removes whatever is stored at `path`, if present.
+    fn remove(&self, path: &Path) -> Result<(), AflError>;
+
+    /// Lists all paths currently stored.
+    fn list(&self) -> Result<Vec<PathBuf>, AflError>;
+
+    /// Returns whether something is stored at `path`.
+    fn exists(&self, path: &Path) -> bool;
+}
+
+/// The default storage backend, backed directly by `std::fs`, rooted at a
+/// single directory (mirroring `OnDiskCorpus::dir_path`).
+#[derive(Clone)]
+pub struct StdFsStorage {
+    root: PathBuf,
+}
+
+impl StdFsStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl CorpusStorage for StdFsStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, AflError> {
+        fs::read(path).map_err(|e| AflError::IllegalState(format!("Could not read {:?}: {:?}", path, e)))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), AflError> {
+        fs::write(path, data).map_err(|e| AflError::IllegalState(format!("Could not write {:?}: {:?}", path, e)))
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), AflError> {
+        fs::remove_file(path).map_err(|e| AflError::IllegalState(format!("Could not remove {:?}: {:?}", path, e)))
+    }
+
+    fn list(&self) -> Result<Vec<PathBuf>, AflError> {
+        let dir = fs::read_dir(&self.root)
+            .map_err(|e| AflError::IllegalState(format!("Could not read dir {:?}: {:?}", self.root, e)))?;
+        let mut entries = Vec::new();
+        for entry in dir {
+            let entry = entry.map_err(|e| AflError::IllegalState(format!("{:?}", e)))?;
+            if entry.path().is_file() {
+                entries.push(entry.path());
+            }
+        }
+        Ok(entries)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+}
+
+/// An in-memory storage backend, so `load_testcase`/`add` can be unit-tested
+/// without touching a real disk.
+#[derive(Default)]
+pub struct InMemoryStorage {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self {
+            files: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl CorpusStorage for InMemoryStorage {
+    fn read(&self, path: &Path) -> Result<Vec<u8>, AflError> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| AflError::KeyNotFound(format!("No such entry: {:?}", path)))
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> Result<(), AflError> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), data.to_vec());
+        Ok(())
+    }
+
+    fn remove(&self, path: &Path) -> Result<(), AflError> {
+        self.files.lock().unwrap().remove(path);
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<PathBuf>, AflError> {
+        Ok(self.files.lock().unwrap().keys().cloned().collect())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_storage_roundtrip() {
+        let storage = InMemoryStorage::new();
+        let path = PathBuf::from("id_0");
+        assert!(!storage.exists(&path));
+        storage.write(&path, b"hello").unwrap();
+        assert!(storage.exists(&path));
+        assert_eq!(storage.read(&path).unwrap(), b"hello");
+        assert_eq!(storage.list().unwrap(), vec![path.clone()]);
+        storage.remove(&path).unwrap();
+        assert!(!storage.exists(&path));
+    }
+}
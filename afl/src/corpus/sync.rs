@@ -0,0 +1,262 @@
+//! AFL-style parallel fuzzing relies on instances importing each other's
+//! finds from a shared directory. `SyncingCorpus` watches a directory for
+//! testcases dropped there by sibling instances and ingests them as new
+//! `Testcase`s, so a harness loop can interleave syncing with `next()`.
+
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::corpus::storage::StdFsStorage;
+use crate::corpus::{Corpus, HasTestcaseVec, Testcase};
+use crate::inputs::Input;
+use crate::utils::Rand;
+use crate::AflError;
+
+/// A corpus wrapper (sibling to `QueueCorpus`) that watches `dir_path` for
+/// testcases written by other fuzzer instances and imports them via `add`.
+///
+/// # Scope note: polling only, no event-notification watcher yet
+///
+/// The request that filed this asked for filesystem event notification
+/// (inotify/kqueue/etc., to import as soon as a peer writes) with polling
+/// only as a fallback. What's implemented here is the polling half only:
+/// `sync_now` lists `dir_path` and imports any filename it hasn't seen yet,
+/// and a harness loop is expected to call it periodically, e.g. whenever
+/// `sync_interval` has elapsed since `last_synced` (`needs_sync` plus a
+/// short `sync_interval` is the intended way to approximate "as soon as a
+/// peer writes" today). An event-notification watcher was left out because
+/// this crate has no dependency on a cross-platform notification library,
+/// and adding raw per-platform FFI for it is a larger change than fit in
+/// this pass — but that's a real scope reduction against what was asked
+/// for, not just an implementation detail, so it shouldn't be treated as
+/// settled by this comment alone. Flagging for whoever filed the request to
+/// confirm the polling-only fallback is acceptable as the shipped behavior,
+/// or to scope the event-notification watcher as separate follow-up work.
+pub struct SyncingCorpus<C, I, R>
+where
+    C: Corpus<I, R>,
+    I: Input,
+    R: Rand,
+{
+    corpus: C,
+    dir_path: PathBuf,
+    sync_interval: Duration,
+    last_synced: SystemTime,
+    /// Filenames already imported (or already in the wrapped corpus when
+    /// constructed), so
+    /// an instance never re-imports its own outputs.
+    seen_filenames: BTreeSet<String>,
+    phantom: PhantomData<(I, R)>,
+}
+
+impl<C, I, R> HasTestcaseVec<I> for SyncingCorpus<C, I, R>
+where
+    C: Corpus<I, R>,
+    I: Input,
+    R: Rand,
+{
+    fn entries(&self) -> &[Testcase<I>] {
+        self.corpus.entries()
+    }
+    fn entries_mut(&mut self) -> &mut Vec<Testcase<I>> {
+        self.corpus.entries_mut()
+    }
+}
+
+impl<C, I, R> Corpus<I, R> for SyncingCorpus<C, I, R>
+where
+    C: Corpus<I, R>,
+    I: Input,
+    R: Rand,
+{
+    fn count(&self) -> usize {
+        self.corpus.count()
+    }
+
+    fn add(&mut self, testcase: Testcase<I>) {
+        // `testcase` may not have a filename yet (e.g. `Testcase::new` on a
+        // fresh local find) — the wrapped corpus (typically `OnDiskCorpus`)
+        // is the one that assigns it on insert, so the filename must be read
+        // back out *after* delegating, or a later `sync_now` would see this
+        // instance's own file on disk and re-import it as a peer's.
+        self.corpus.add(testcase);
+        if let Some(filename) = self.corpus.entries().last().and_then(|t| t.filename().clone()) {
+            self.seen_filenames.insert(filename);
+        }
+    }
+
+    fn remove(&mut self, entry: &Testcase<I>) -> Option<Testcase<I>> {
+        self.corpus.remove(entry)
+    }
+
+    fn random_entry(&self, rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
+        self.corpus.random_entry(rand)
+    }
+
+    fn load_testcase(&mut self, idx: usize) -> Result<(), AflError> {
+        self.corpus.load_testcase(idx)
+    }
+
+    fn current_testcase(&self) -> (&Testcase<I>, usize) {
+        self.corpus.current_testcase()
+    }
+
+    fn next(&mut self, rand: &mut R) -> Result<(&Testcase<I>, usize), AflError> {
+        self.corpus.next(rand)
+    }
+}
+
+impl<C, I, R> SyncingCorpus<C, I, R>
+where
+    C: Corpus<I, R>,
+    I: Input,
+    R: Rand,
+{
+    pub fn new(corpus: C, dir_path: PathBuf) -> Self {
+        let seen_filenames = corpus
+            .entries()
+            .iter()
+            .filter_map(|t| t.filename().clone())
+            .collect();
+        Self {
+            corpus,
+            dir_path,
+            sync_interval: Duration::from_secs(5),
+            last_synced: SystemTime::now(),
+            seen_filenames,
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn sync_interval(&self) -> Duration {
+        self.sync_interval
+    }
+
+    pub fn set_sync_interval(&mut self, sync_interval: Duration) {
+        self.sync_interval = sync_interval;
+    }
+
+    pub fn last_synced(&self) -> SystemTime {
+        self.last_synced
+    }
+
+    /// Whether `sync_interval` has elapsed since the last sync, so a harness
+    /// loop can decide whether to call `sync_now`.
+    pub fn needs_sync(&self) -> bool {
+        self.last_synced
+            .elapsed()
+            .map(|elapsed| elapsed >= self.sync_interval)
+            .unwrap_or(true)
+    }
+
+    /// Scans `dir_path` and imports any testcase not yet seen, returning how
+    /// many new testcases were imported.
+    pub fn sync_now(&mut self) -> Result<usize, AflError> {
+        let storage = StdFsStorage::new(self.dir_path.clone());
+        let read_dir = fs::read_dir(&self.dir_path).map_err(|e| {
+            AflError::IllegalState(format!("Could not read sync dir {:?}: {:?}", self.dir_path, e))
+        })?;
+
+        let mut imported = 0;
+        for entry in read_dir {
+            let entry =
+                entry.map_err(|e| AflError::IllegalState(format!("Could not list entry: {:?}", e)))?;
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            // The index `OnDiskCorpus` keeps in its directory is not a testcase.
+            if path.file_name().and_then(|n| n.to_str())
+                == Some(crate::corpus::index::INDEX_FILENAME)
+            {
+                continue;
+            }
+            let filename = path
+                .to_str()
+                .ok_or_else(|| AflError::IllegalState(format!("Invalid path: {:?}", path)))?
+                .to_owned();
+            if self.seen_filenames.contains(&filename) {
+                continue;
+            }
+
+            let testcase = Testcase::<I>::load_from_disk(&storage, &filename)?;
+            self.seen_filenames.insert(filename);
+            self.corpus.add(testcase);
+            imported += 1;
+        }
+
+        self.last_synced = SystemTime::now();
+        Ok(imported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corpus::InMemoryCorpus;
+    use crate::inputs::bytes::BytesInput;
+    use crate::utils::StdRand;
+
+    static NONCE_FOR_TEST_DIR: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+    #[test]
+    fn test_sync_now_dedupes_already_seen_files() {
+        let dir = std::env::temp_dir().join(format!(
+            "libafl_sync_test_{}_{}",
+            std::process::id(),
+            NONCE_FOR_TEST_DIR.fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("from_peer_1"), b"aaaa").unwrap();
+
+        let mut syncing = SyncingCorpus::<InMemoryCorpus<BytesInput, StdRand>, BytesInput, StdRand>::new(
+            InMemoryCorpus::new(),
+            dir.clone(),
+        );
+        assert_eq!(syncing.sync_now().unwrap(), 1);
+        assert_eq!(syncing.count(), 1);
+        // Nothing new appeared, so a second sync imports nothing.
+        assert_eq!(syncing.sync_now().unwrap(), 0);
+        assert_eq!(syncing.count(), 1);
+
+        fs::write(dir.join("from_peer_2"), b"bbbb").unwrap();
+        assert_eq!(syncing.sync_now().unwrap(), 1);
+        assert_eq!(syncing.count(), 2);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_add_does_not_self_reimport_ondisk_testcase() {
+        use crate::corpus::OnDiskCorpus;
+
+        let dir = std::env::temp_dir().join(format!(
+            "libafl_sync_selfimport_test_{}_{}",
+            std::process::id(),
+            NONCE_FOR_TEST_DIR.fetch_add(1, core::sync::atomic::Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut syncing = SyncingCorpus::<OnDiskCorpus<BytesInput, StdRand>, BytesInput, StdRand>::new(
+            OnDiskCorpus::new(dir.clone()),
+            dir.clone(),
+        );
+        // A fresh local find has no filename yet; `OnDiskCorpus::add`
+        // assigns one on insert.
+        syncing.add(Testcase::new(BytesInput::new(vec![1, 2, 3])));
+        assert_eq!(syncing.count(), 1);
+
+        // The file the local add just wrote must already be marked seen, so
+        // syncing does not reimport it as if a peer had written it.
+        assert_eq!(syncing.sync_now().unwrap(), 0);
+        assert_eq!(syncing.count(), 1);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,175 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::corpus::storage::CorpusStorage;
+use crate::inputs::Input;
+use crate::AflError;
+
+/// Metadata attached to a `Testcase`, separate from the raw input bytes.
+#[derive(Clone)]
+pub struct TestcaseMetadata {
+    /// How many times this testcase has been fuzzed
+    fuzz_level: u64,
+    /// Relative energy/weight for weighted scheduling; higher means this
+    /// testcase is picked more often. Defaults to 1.0, i.e. no bias.
+    weight: f64,
+}
+
+impl TestcaseMetadata {
+    pub fn new() -> Self {
+        Self {
+            fuzz_level: 0,
+            weight: 1.0,
+        }
+    }
+
+    pub fn fuzz_level(&self) -> u64 {
+        self.fuzz_level
+    }
+
+    pub fn set_fuzz_level(&mut self, fuzz_level: u64) {
+        self.fuzz_level = fuzz_level;
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+}
+
+/// A testcase, in memory or on disk.
+pub struct Testcase<I>
+where
+    I: Input,
+{
+    input: Option<I>,
+    filename: Option<String>,
+    metadata: TestcaseMetadata,
+    /// Whether `input` has changed since it was last persisted to storage
+    dirty: bool,
+}
+
+impl<I> Testcase<I>
+where
+    I: Input,
+{
+    /// The loaded input, if any
+    pub fn input(&self) -> &Option<I> {
+        &self.input
+    }
+
+    /// The loaded input, if any (mutable)
+    pub fn input_mut(&mut self) -> &mut Option<I> {
+        &mut self.input
+    }
+
+    pub fn set_input(&mut self, input: I) {
+        self.input = Some(input);
+        self.dirty = true;
+    }
+
+    /// Whether the loaded input has changed since it was last persisted
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Drops the loaded input, keeping the filename so it can be reloaded
+    /// later. The caller is responsible for persisting a dirty input first,
+    /// e.g. via `save_to_disk`.
+    pub fn unload(&mut self) {
+        self.input = None;
+        self.dirty = false;
+    }
+
+    /// The filename on disk, if any
+    pub fn filename(&self) -> &Option<String> {
+        &self.filename
+    }
+
+    pub fn set_filename(&mut self, filename: String) {
+        self.filename = Some(filename);
+    }
+
+    pub fn metadata(&self) -> &TestcaseMetadata {
+        &self.metadata
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut TestcaseMetadata {
+        &mut self.metadata
+    }
+
+    /// This testcase's relative energy/weight for weighted scheduling.
+    pub fn weight(&self) -> f64 {
+        self.metadata.weight()
+    }
+
+    pub fn set_weight(&mut self, weight: f64) {
+        self.metadata.set_weight(weight);
+    }
+
+    pub fn new(input: I) -> Self {
+        Self {
+            input: Some(input),
+            filename: None,
+            metadata: TestcaseMetadata::new(),
+            dirty: true,
+        }
+    }
+
+    pub fn with_filename(input: I, filename: String) -> Self {
+        Self {
+            input: Some(input),
+            filename: Some(filename),
+            metadata: TestcaseMetadata::new(),
+            dirty: true,
+        }
+    }
+
+    /// Creates a testcase known only by its filename, with no input loaded
+    /// yet, e.g. when reconstructing entries from an on-disk index.
+    pub fn unloaded(filename: String) -> Self {
+        Self {
+            input: None,
+            filename: Some(filename),
+            metadata: TestcaseMetadata::new(),
+            dirty: false,
+        }
+    }
+
+    /// Loads a testcase from a given storage backend, by filename.
+    #[cfg(feature = "std")]
+    pub fn load_from_disk<S>(storage: &S, filename: &str) -> Result<Self, AflError>
+    where
+        S: CorpusStorage,
+    {
+        let bytes = storage.read(Path::new(filename))?;
+        let input = I::from_bytes(&bytes)?;
+        let mut testcase = Self::with_filename(input, filename.into());
+        testcase.dirty = false;
+        Ok(testcase)
+    }
+
+    /// Persists this testcase's input to the given storage backend. Fails if
+    /// the testcase has no filename or no loaded input.
+    #[cfg(feature = "std")]
+    pub fn save_to_disk<S>(&mut self, storage: &S) -> Result<(), AflError>
+    where
+        S: CorpusStorage,
+    {
+        let filename = self.filename.as_ref().ok_or_else(|| {
+            AflError::IllegalState("Cannot save a testcase without a filename".into())
+        })?;
+        let input = self.input.as_ref().ok_or_else(|| {
+            AflError::IllegalState("Cannot save a testcase with no loaded input".into())
+        })?;
+        storage.write(Path::new(filename), &input.to_bytes())?;
+        self.dirty = false;
+        Ok(())
+    }
+}